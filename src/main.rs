@@ -1,18 +1,99 @@
 use core::panic;
 use std::env;
+use std::process;
+mod manifest;
 mod transaction_processor;
 
+const DEFAULT_MANIFEST: &str = ".processed_files";
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        panic!("No argument found for transactions file");
+    let mut filename = None;
+    let mut manifest_path = String::from(DEFAULT_MANIFEST);
+    let mut allow_duplicate = false;
+    let mut output_split = None;
+    let mut output_dir = None;
+    let mut dispute_export = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--manifest" => {
+                manifest_path = args
+                    .next()
+                    .unwrap_or_else(|| panic!("No value found for --manifest"))
+            }
+            "--allow-duplicate" => allow_duplicate = true,
+            "--output-split" => {
+                let shards = args
+                    .next()
+                    .unwrap_or_else(|| panic!("No value found for --output-split"));
+                output_split = Some(
+                    shards
+                        .parse::<u16>()
+                        .unwrap_or_else(|_| panic!("Invalid value for --output-split: {}", shards)),
+                )
+            }
+            "--output-dir" => {
+                output_dir = Some(
+                    args.next()
+                        .unwrap_or_else(|| panic!("No value found for --output-dir")),
+                )
+            }
+            "--dispute-export" => {
+                dispute_export = Some(
+                    args.next()
+                        .unwrap_or_else(|| panic!("No value found for --dispute-export")),
+                )
+            }
+            _ if arg.starts_with("--") => panic!("Unknown argument: {}", arg),
+            _ if filename.is_some() => panic!("Unexpected argument: {}", arg),
+            _ => filename = Some(arg),
+        }
+    }
+    if output_dir.is_some() && output_split.is_none() {
+        panic!("--output-dir can only be used with --output-split");
     }
-    let filename = &args[1];
+    let filename = filename.unwrap_or_else(|| panic!("No argument found for transactions file"));
+
+    let mut manifest =
+        manifest::Manifest::load(&manifest_path).expect("Error reading processed files manifest");
+    let checksum = manifest::file_checksum(&filename)
+        .unwrap_or_else(|_| panic!("Unable to open {}", filename));
+    let previous = manifest.find(checksum).map(String::from);
+    if let Some(previous) = &previous {
+        if !allow_duplicate {
+            eprintln!(
+                "{} has already been processed (as {}). Use --allow-duplicate to process it again.",
+                filename, previous
+            );
+            process::exit(1);
+        }
+        eprintln!(
+            "Warning: {} has already been processed (as {}).",
+            filename, previous
+        );
+    }
+
     let mut tx_processor = transaction_processor::TransactionProcessor::new();
     tx_processor
-        .stream_csv(filename)
+        .stream_csv(&filename)
         .expect("Error reading csv file");
-    tx_processor
-        .print_client_accounts()
-        .expect("Error printing status of client accounts");
+    if let Some(path) = dispute_export {
+        tx_processor
+            .write_dispute_cases(&path)
+            .expect("Error writing dispute cases");
+    }
+    match output_split {
+        Some(shards) => tx_processor
+            .write_split_client_accounts(output_dir.as_deref().unwrap_or("."), shards)
+            .expect("Error writing split client accounts"),
+        None => tx_processor
+            .print_client_accounts()
+            .expect("Error printing status of client accounts"),
+    }
+    // Only record the file once all output has been written, so a failed run can be retried
+    if previous.is_none() {
+        manifest
+            .record(checksum, &filename)
+            .expect("Error updating processed files manifest");
+    }
 }
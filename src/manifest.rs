@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Record of input files that have already been processed, keyed by a checksum of their contents.
+/// Used to stop the same file being applied twice, e.g. re-running a day's deposits.
+pub struct Manifest {
+    path: PathBuf,
+    /// Checksum of each processed file and the name it was processed under
+    entries: HashMap<u64, String>,
+}
+
+impl Manifest {
+    // Load the manifest at path. A missing manifest is treated as empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Manifest, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = HashMap::new();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let (checksum, filename) = line
+                .split_once(' ')
+                .ok_or(format!("Malformed manifest entry: {}", line))?;
+            entries.insert(u64::from_str_radix(checksum, 16)?, filename.to_string());
+        }
+        Ok(Manifest { path, entries })
+    }
+
+    /// Returns the name of the file previously processed with this checksum, if any.
+    pub fn find(&self, checksum: u64) -> Option<&str> {
+        self.entries.get(&checksum).map(String::as_str)
+    }
+
+    // Append an entry to the manifest file so it persists across runs.
+    pub fn record(&mut self, checksum: u64, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{:016x} {}", checksum, filename)?;
+        self.entries.insert(checksum, filename.to_string());
+        Ok(())
+    }
+}
+
+/// 64-bit FNV-1a checksum of a file's contents.
+pub fn file_checksum<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    checksum(fs::File::open(path)?)
+}
+
+// Hash in buffered chunks so large input files aren't read into memory
+fn checksum<R: Read>(reader: R) -> io::Result<u64> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut reader = BufReader::new(reader);
+    let mut hash = OFFSET_BASIS;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(hash);
+        }
+        hash = buffer.iter().fold(hash, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        });
+        let length = buffer.len();
+        reader.consume(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_checksum_differs_for_different_contents() {
+        // Arrange
+        let first = b"type,client,tx,amount\ndeposit,1,1,1.0\n";
+        let second = b"type,client,tx,amount\ndeposit,1,1,2.0\n";
+
+        // Act / Assert
+        assert_eq!(checksum(&first[..]).unwrap(), checksum(&first[..]).unwrap());
+        assert_ne!(
+            checksum(&first[..]).unwrap(),
+            checksum(&second[..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_manifest_remembers_recorded_files_across_loads() {
+        // Arrange
+        let path = env::temp_dir().join(format!("tx-manifest-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut manifest = Manifest::load(&path).unwrap();
+        assert_eq!(manifest.find(42), None);
+
+        // Act
+        manifest.record(42, "transactions.csv").unwrap();
+        let reloaded = Manifest::load(&path).unwrap();
+
+        // Assert
+        assert_eq!(reloaded.find(42), Some("transactions.csv"));
+        assert_eq!(reloaded.find(43), None);
+        fs::remove_file(&path).unwrap();
+    }
+}
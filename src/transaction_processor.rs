@@ -1,13 +1,22 @@
-use serde::{Deserialize, Serialize, Serializer};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::error::Error;
+use std::fs;
 use std::io;
+use std::path::Path;
+use transaction_processor_types::{Action, ClientAccount, Record};
 
 pub struct TransactionProcessor {
     /// Keep track of all client accounts and associated values
     accounts: HashMap<u16, ClientAccount>,
     /// Keep basic info on deposit and withdrawal transactions so that we can handle disputes/chargebacks
     transaction_log: HashMap<u32, Record>,
+    /// Every dispute raised, in the order it was raised, for exporting to case management
+    dispute_cases: Vec<DisputeCase>,
+    /// Index into dispute_cases of the open dispute for each transaction
+    open_disputes: HashMap<u32, usize>,
+    /// Number of records read so far. Used to order dispute status transitions.
+    records_processed: u64,
 }
 
 impl TransactionProcessor {
@@ -15,26 +24,34 @@ impl TransactionProcessor {
         TransactionProcessor {
             accounts: HashMap::new(),
             transaction_log: HashMap::new(),
+            dispute_cases: Vec::new(),
+            open_disputes: HashMap::new(),
+            records_processed: 0,
         }
     }
 
     pub fn stream_csv(&mut self, filename: &String) -> Result<(), Box<dyn Error>> {
         let mut rdr = csv::Reader::from_path(filename)
-            .expect(format!("Unable to open {}", filename).as_str());
+            .unwrap_or_else(|_| panic!("Unable to open {}", filename));
 
         for result in rdr.deserialize() {
             let record: Record = result?;
-            match record.action {
-                Action::Deposit => self.handle_deposit(record),
-                Action::Withdrawal => self.handle_withdrawal(record),
-                Action::Dispute => self.handle_dispute(record),
-                Action::Resolve => self.handle_resolve(record),
-                Action::Chargeback => self.handle_chargeback(record),
-            }
+            self.process_record(record);
         }
         Ok(())
     }
 
+    fn process_record(&mut self, record: Record) {
+        self.records_processed += 1;
+        match record.action {
+            Action::Deposit => self.handle_deposit(record),
+            Action::Withdrawal => self.handle_withdrawal(record),
+            Action::Dispute => self.handle_dispute(record),
+            Action::Resolve => self.handle_resolve(record),
+            Action::Chargeback => self.handle_chargeback(record),
+        }
+    }
+
     // Increase clients available and total by deposit amount. If client account does not exist, create it.
     fn handle_deposit(&mut self, deposit: Record) {
         let client = self.accounts.get_mut(&deposit.client);
@@ -85,6 +102,16 @@ impl TransactionProcessor {
                 account.available -= tx
                     .amount
                     .expect("Transaction referenced in a dispute did not have a value.");
+                self.open_disputes
+                    .insert(dispute.transaction, self.dispute_cases.len());
+                self.dispute_cases.push(DisputeCase {
+                    client: dispute.client,
+                    transaction: dispute.transaction,
+                    amount: tx.amount,
+                    status: DisputeStatus::Open,
+                    opened_row: self.records_processed,
+                    closed_row: None,
+                });
             }
         }
     }
@@ -99,6 +126,7 @@ impl TransactionProcessor {
                 account.available += tx
                     .amount
                     .expect("Transaction referenced in a resolution did not have a value.");
+                self.close_dispute(resolve.transaction, DisputeStatus::Resolved);
             }
         }
     }
@@ -114,62 +142,118 @@ impl TransactionProcessor {
                     .amount
                     .expect("Transaction referenced in a chargeback did not have a value.");
                 account.locked = true;
+                self.close_dispute(chargeback.transaction, DisputeStatus::ChargedBack);
             }
         }
     }
 
+    fn close_dispute(&mut self, transaction: u32, status: DisputeStatus) {
+        if let Some(index) = self.open_disputes.remove(&transaction) {
+            let case = &mut self.dispute_cases[index];
+            case.status = status;
+            case.closed_row = Some(self.records_processed);
+        }
+    }
+
     pub fn print_client_accounts(&self) -> Result<(), Box<dyn Error>> {
         let mut writer = csv::Writer::from_writer(io::stdout());
-        for (_, account) in &self.accounts {
+        for account in self.accounts.values() {
             writer.serialize(account)?;
         }
         writer.flush()?;
         Ok(())
     }
+
+    /// Write one row per dispute raised to a csv file for import into case management.
+    pub fn write_dispute_cases<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::Writer::from_path(path)?;
+        for case in &self.dispute_cases {
+            writer.serialize(case)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write client accounts across `shards` csv files in `output_dir`, assigning each client to
+    /// shard `client % shards`, plus an index file listing each shard file and its row count.
+    pub fn write_split_client_accounts<P: AsRef<Path>>(
+        &self,
+        output_dir: P,
+        shards: u16,
+    ) -> Result<(), Box<dyn Error>> {
+        if shards == 0 {
+            return Err("Number of output shards must be greater than zero".into());
+        }
+        let output_dir = output_dir.as_ref();
+        fs::create_dir_all(output_dir)?;
+
+        let mut writers = Vec::with_capacity(shards as usize);
+        let mut index = Vec::with_capacity(shards as usize);
+        for shard in 0..shards {
+            let file = format!("accounts_{}.csv", shard);
+            // Header is written explicitly so empty shards are still valid csv for the loader
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_path(output_dir.join(&file))?;
+            writer.write_record(ClientAccount::HEADERS)?;
+            writers.push(writer);
+            index.push(ShardIndexEntry {
+                shard,
+                file,
+                clients: 0,
+            });
+        }
+
+        for account in self.accounts.values() {
+            let shard = (account.client % shards) as usize;
+            writers[shard].serialize(account)?;
+            index[shard].clients += 1;
+        }
+        for writer in &mut writers {
+            writer.flush()?;
+        }
+
+        let mut index_writer = csv::Writer::from_path(output_dir.join("accounts_index.csv"))?;
+        for entry in &index {
+            index_writer.serialize(entry)?;
+        }
+        index_writer.flush()?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct Record {
-    #[serde(rename = "type")]
-    action: Action,
-    client: u16,
-    #[serde(rename = "tx")]
-    transaction: u32,
-    amount: Option<f32>,
+/// Row of the index file written alongside split account output
+#[derive(Serialize)]
+struct ShardIndexEntry {
+    shard: u16,
+    /// File name of the shard, relative to the index file
+    file: String,
+    /// Number of client accounts written to the shard
+    clients: usize,
 }
 
+/// Row of the dispute cases export
 #[derive(Serialize)]
-struct ClientAccount {
-    /// Client Id
+struct DisputeCase {
     client: u16,
-    /// Total funds available for trading. available = total - held.
-    #[serde(serialize_with = "four_decimal_serializer")]
-    available: f32,
-    /// Total funds held for dispute. held = total - available
-    #[serde(serialize_with = "four_decimal_serializer")]
-    held: f32,
-    /// Total funds available or held. Total = available + held.
-    #[serde(serialize_with = "four_decimal_serializer")]
-    total: f32,
-    /// Account is locked if charge back occurs
-    locked: bool,
+    #[serde(rename = "tx")]
+    transaction: u32,
+    /// Amount of the disputed transaction
+    amount: Option<f32>,
+    status: DisputeStatus,
+    /// Data row (excluding header) of the dispute. There are no timestamps in the input, so row
+    /// numbers give the order.
+    opened_row: u64,
+    /// Data row (excluding header) of the resolve or chargeback that closed the dispute
+    closed_row: Option<u64>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
-enum Action {
-    Deposit,
-    Withdrawal,
-    Dispute,
-    Resolve,
-    Chargeback,
-}
-
-fn four_decimal_serializer<S>(x: &f32, s: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    s.serialize_str(format!("{:.4}", x).as_str())
+enum DisputeStatus {
+    Open,
+    Resolved,
+    ChargedBack,
 }
 
 #[cfg(test)]
@@ -311,7 +395,7 @@ mod tests {
         assert_eq!(tx_processor.accounts.get(&2).unwrap().available, 100.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().total, 100.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().held, 0.0);
-        assert_eq!(tx_processor.accounts.get(&2).unwrap().locked, false);
+        assert!(!tx_processor.accounts.get(&2).unwrap().locked);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().client, 2);
     }
 
@@ -351,7 +435,7 @@ mod tests {
         assert_eq!(tx_processor.accounts.get(&2).unwrap().available, 75.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().held, 25.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().total, 100.0);
-        assert_eq!(tx_processor.accounts.get(&2).unwrap().locked, false);
+        assert!(!tx_processor.accounts.get(&2).unwrap().locked);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().client, 2);
     }
 
@@ -390,7 +474,7 @@ mod tests {
         assert_eq!(tx_processor.accounts.get(&2).unwrap().held, 0.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().available, 100.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().total, 100.0);
-        assert_eq!(tx_processor.accounts.get(&2).unwrap().locked, false);
+        assert!(!tx_processor.accounts.get(&2).unwrap().locked);
     }
 
     #[test]
@@ -421,7 +505,7 @@ mod tests {
         assert_eq!(tx_processor.accounts.get(&2).unwrap().available, 100.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().total, 100.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().held, 0.0);
-        assert_eq!(tx_processor.accounts.get(&2).unwrap().locked, false);
+        assert!(!tx_processor.accounts.get(&2).unwrap().locked);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().client, 2);
     }
 
@@ -452,7 +536,7 @@ mod tests {
         // Assert
         assert_eq!(tx_processor.accounts.get(&2).unwrap().available, 100.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().held, 0.0);
-        assert_eq!(tx_processor.accounts.get(&2).unwrap().locked, false);
+        assert!(!tx_processor.accounts.get(&2).unwrap().locked);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().client, 2);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().total, 100.0);
     }
@@ -490,8 +574,123 @@ mod tests {
         // Assert
         assert_eq!(tx_processor.accounts.get(&2).unwrap().available, 75.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().held, 0.0);
-        assert_eq!(tx_processor.accounts.get(&2).unwrap().locked, true);
+        assert!(tx_processor.accounts.get(&2).unwrap().locked);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().total, 75.0);
         assert_eq!(tx_processor.accounts.get(&2).unwrap().client, 2);
     }
+
+    #[test]
+    fn test_split_output_writes_clients_to_shards_and_index() {
+        // Arrange
+        let mut tx_processor = TransactionProcessor::new();
+        for client in 1..=5 {
+            tx_processor.handle_deposit(Record {
+                client,
+                action: Action::Deposit,
+                transaction: client as u32,
+                amount: Some(10.0),
+            });
+        }
+        let output_dir = std::env::temp_dir().join(format!("tx-split-{}", std::process::id()));
+
+        // Act
+        tx_processor
+            .write_split_client_accounts(&output_dir, 2)
+            .unwrap();
+
+        // Assert
+        let index = fs::read_to_string(output_dir.join("accounts_index.csv")).unwrap();
+        assert_eq!(
+            index,
+            "shard,file,clients\n0,accounts_0.csv,2\n1,accounts_1.csv,3\n"
+        );
+        let expected_clients = vec![vec![2, 4], vec![1, 3, 5]];
+        for (shard, expected_clients) in expected_clients.into_iter().enumerate() {
+            let contents =
+                fs::read_to_string(output_dir.join(format!("accounts_{}.csv", shard))).unwrap();
+            let mut lines = contents.lines();
+            assert_eq!(lines.next(), Some("client,available,held,total,locked"));
+            let mut rows: Vec<&str> = lines.collect();
+            rows.sort_unstable();
+            let expected_rows: Vec<String> = expected_clients
+                .iter()
+                .map(|client| format!("{},10.0000,0.0000,10.0000,false", client))
+                .collect();
+            assert_eq!(rows, expected_rows);
+        }
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_output_rejects_zero_shards() {
+        // Arrange
+        let tx_processor = TransactionProcessor::new();
+
+        // Act
+        let result = tx_processor.write_split_client_accounts(std::env::temp_dir(), 0);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dispute_cases_track_status_transitions() {
+        // Arrange
+        let mut tx_processor = TransactionProcessor::new();
+        let records = vec![
+            (Action::Deposit, 1, Some(50.0)),
+            (Action::Deposit, 2, Some(25.0)),
+            (Action::Dispute, 1, None),
+            (Action::Dispute, 2, None),
+            (Action::Resolve, 1, None),
+            (Action::Chargeback, 2, None),
+            (Action::Dispute, 1, None),
+        ];
+
+        // Act
+        for (action, transaction, amount) in records {
+            tx_processor.process_record(Record {
+                client: 2,
+                action,
+                transaction,
+                amount,
+            });
+        }
+
+        // Assert
+        let cases = &tx_processor.dispute_cases;
+        assert_eq!(cases.len(), 3);
+        assert_eq!(cases[0].transaction, 1);
+        assert_eq!(cases[0].status, DisputeStatus::Resolved);
+        assert_eq!((cases[0].opened_row, cases[0].closed_row), (3, Some(5)));
+        assert_eq!(cases[1].transaction, 2);
+        assert_eq!(cases[1].status, DisputeStatus::ChargedBack);
+        assert_eq!((cases[1].opened_row, cases[1].closed_row), (4, Some(6)));
+        assert_eq!(cases[2].status, DisputeStatus::Open);
+        assert_eq!((cases[2].opened_row, cases[2].closed_row), (7, None));
+    }
+
+    #[test]
+    fn test_dispute_cases_ignore_dispute_for_non_existing_transaction() {
+        // Arrange
+        let mut tx_processor = TransactionProcessor::new();
+        tx_processor.handle_deposit(Record {
+            client: 2,
+            action: Action::Deposit,
+            transaction: 1,
+            amount: Some(10.0),
+        });
+        let dispute = Record {
+            client: 2,
+            action: Action::Dispute,
+            transaction: 5,
+            amount: None,
+        };
+
+        // Act
+        tx_processor.handle_dispute(dispute);
+
+        // Assert
+        assert!(tx_processor.dispute_cases.is_empty());
+    }
 }
@@ -0,0 +1,192 @@
+//! Data model shared between transaction-processor and the services producing its input.
+//!
+//! These types define the csv format the engine reads and writes, so changes follow semver:
+//! any change to a field, variant or serialized name is a breaking change and needs a major
+//! version bump.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A single row of transaction input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    #[serde(rename = "type")]
+    pub action: Action,
+    pub client: u16,
+    #[serde(rename = "tx")]
+    pub transaction: u32,
+    /// Present for deposits and withdrawals. Disputes, resolves and chargebacks refer to the
+    /// amount of the transaction they reference.
+    pub amount: Option<f32>,
+}
+
+/// Balance of a single client, as written to the engine's output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientAccount {
+    /// Client Id
+    pub client: u16,
+    /// Total funds available for trading. available = total - held.
+    #[serde(
+        serialize_with = "four_decimal_serializer",
+        deserialize_with = "four_decimal_deserializer"
+    )]
+    pub available: f32,
+    /// Total funds held for dispute. held = total - available
+    #[serde(
+        serialize_with = "four_decimal_serializer",
+        deserialize_with = "four_decimal_deserializer"
+    )]
+    pub held: f32,
+    /// Total funds available or held. Total = available + held.
+    #[serde(
+        serialize_with = "four_decimal_serializer",
+        deserialize_with = "four_decimal_deserializer"
+    )]
+    pub total: f32,
+    /// Account is locked if charge back occurs
+    pub locked: bool,
+}
+
+impl ClientAccount {
+    /// Csv header written for client accounts, for writers that need it before any rows exist.
+    pub const HEADERS: [&'static str; 5] = ["client", "available", "held", "total", "locked"];
+}
+
+/// Type of a transaction record, serialized in snake_case (e.g. `deposit`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Deposit,
+    Withdrawal,
+    Dispute,
+    Resolve,
+    Chargeback,
+}
+
+fn four_decimal_serializer<S>(x: &f32, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(format!("{:.4}", x).as_str())
+}
+
+// Counterpart to four_decimal_serializer, which writes amounts as strings
+fn four_decimal_deserializer<'de, D>(d: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(d)?;
+    value.parse().map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_round_trips_through_csv() {
+        // Arrange
+        let records = vec![
+            Record {
+                action: Action::Deposit,
+                client: 1,
+                transaction: 1,
+                amount: Some(1.5),
+            },
+            Record {
+                action: Action::Dispute,
+                client: 1,
+                transaction: 1,
+                amount: None,
+            },
+        ];
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        // Act
+        for record in &records {
+            writer.serialize(record).unwrap();
+        }
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let read: Vec<Record> = csv::Reader::from_reader(output.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            output,
+            "type,client,tx,amount\ndeposit,1,1,1.5\ndispute,1,1,\n"
+        );
+        assert_eq!(read, records);
+    }
+
+    #[test]
+    fn test_client_account_serializes_to_four_decimals() {
+        // Arrange
+        let account = ClientAccount {
+            client: 3,
+            available: 78.4,
+            held: 20.8,
+            total: 99.2,
+            locked: false,
+        };
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        // Act
+        writer.serialize(&account).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        // Assert
+        assert_eq!(
+            output,
+            "client,available,held,total,locked\n3,78.4000,20.8000,99.2000,false\n"
+        );
+    }
+
+    #[test]
+    fn test_client_account_headers_match_serialized_fields() {
+        // Arrange
+        let account = ClientAccount {
+            client: 1,
+            available: 0.0,
+            held: 0.0,
+            total: 0.0,
+            locked: false,
+        };
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        // Act
+        writer.serialize(&account).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        // Assert
+        assert_eq!(
+            output.lines().next().unwrap(),
+            ClientAccount::HEADERS.join(",")
+        );
+    }
+
+    #[test]
+    fn test_client_account_round_trips_through_csv() {
+        // Arrange
+        let account = ClientAccount {
+            client: 3,
+            available: 78.5,
+            held: 20.25,
+            total: 98.75,
+            locked: true,
+        };
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        // Act
+        writer.serialize(&account).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let read: Vec<ClientAccount> = csv::Reader::from_reader(output.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // Assert
+        assert_eq!(read, vec![account]);
+    }
+}